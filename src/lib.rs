@@ -1,6 +1,11 @@
-use std::path::PathBuf;
+use std::ffi::OsString;
+use std::num::ParseIntError;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use thiserror::Error;
+#[cfg(windows)]
 use winreg::RegKey;
+#[cfg(windows)]
 use winreg::enums::HKEY_LOCAL_MACHINE;
 
 #[derive(Debug, Error)]
@@ -11,6 +16,125 @@ pub enum Error {
     DirectoryNotFound,
 }
 
+/// A parsed Windows SDK version, e.g. `10.0.19041.0`.
+///
+/// Unlike comparing the raw directory name as a string, [`SdkVersion`] compares the four
+/// dotted components numerically, so `10.0.19041.0` correctly orders after `10.0.9600.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SdkVersion(u32, u32, u32, u32);
+
+#[derive(Debug, Error)]
+pub enum ParseSdkVersionError {
+    #[error(transparent)]
+    InvalidComponent(#[from] ParseIntError),
+    #[error("expected exactly 4 dot-separated components, e.g. `10.0.19041.0`")]
+    WrongComponentCount,
+}
+
+impl SdkVersion {
+    fn from_path(path: &Path) -> Option<Self> {
+        let name = path.components().next_back()?.as_os_str().to_str()?;
+
+        if !name.starts_with("10.") {
+            return None;
+        }
+
+        name.parse().ok()
+    }
+}
+
+impl FromStr for SdkVersion {
+    type Err = ParseSdkVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('.');
+
+        let mut next = || parts.next().ok_or(ParseSdkVersionError::WrongComponentCount);
+
+        let major = next()?.parse()?;
+        let minor = next()?.parse()?;
+        let build = next()?.parse()?;
+        let revision = next()?.parse()?;
+
+        if parts.next().is_some() {
+            // Reject trailing components, e.g. a `.backup` suffix.
+            return Err(ParseSdkVersionError::WrongComponentCount);
+        }
+
+        Ok(Self(major, minor, build, revision))
+    }
+}
+
+impl std::fmt::Display for SdkVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}.{}", self.0, self.1, self.2, self.3)
+    }
+}
+
+/// A target architecture, matching the `Lib\<ver>\<component>\<arch>` and `bin\<ver>\<arch>`
+/// subfolders of the Windows SDK.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    X86,
+    X64,
+    Arm,
+    Arm64,
+}
+
+impl Arch {
+    /// Maps a Rust target triple, e.g. `x86_64-pc-windows-msvc`, to the matching SDK
+    /// architecture directory.
+    pub fn from_target_triple(triple: &str) -> Option<Self> {
+        let arch = triple.split('-').next()?;
+
+        match arch {
+            "i586" | "i686" => Some(Self::X86),
+            "x86_64" => Some(Self::X64),
+            "arm" | "thumbv7a" => Some(Self::Arm),
+            "aarch64" => Some(Self::Arm64),
+            _ => None,
+        }
+    }
+
+    fn as_dir_name(&self) -> &'static str {
+        match self {
+            Self::X86 => "x86",
+            Self::X64 => "x64",
+            Self::Arm => "arm",
+            Self::Arm64 => "arm64",
+        }
+    }
+}
+
+/// An SDK sub-component. Headers live under `Include\<ver>\<component>` for every variant;
+/// [`Component::Ucrt`] and [`Component::Um`] additionally have architecture-specific libraries
+/// under `Lib\<ver>\<component>\<arch>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    /// The Universal CRT, introduced with VS2015.
+    Ucrt,
+    /// The Win32 API.
+    Um,
+    /// Headers shared between [`Component::Ucrt`] and [`Component::Um`].
+    Shared,
+    /// The WinRT headers.
+    WinRt,
+    /// The C++/WinRT headers.
+    CppWinRt,
+}
+
+impl Component {
+    fn as_dir_name(&self) -> &'static str {
+        match self {
+            Self::Ucrt => "ucrt",
+            Self::Um => "um",
+            Self::Shared => "shared",
+            Self::WinRt => "winrt",
+            Self::CppWinRt => "cppwinrt",
+        }
+    }
+}
+
 pub enum DirectoryType {
     /// Get the path to the binaries.
     Binaries,
@@ -25,16 +149,40 @@ pub struct WindowsKits {
 }
 
 impl WindowsKits {
-    /// Sets up a new `WindowsKits` instance by querying SOFTWARE\Microsoft\Windows Kits\Installed
-    /// Roots for the path to the directory containing the Windows SDKs.
+    /// Sets up a new `WindowsKits` instance. The root directory is resolved, in order, from the
+    /// `WINDOWS_KITS_10` environment variable, the `WindowsSdkDir` environment variable (as set
+    /// by a Developer Command Prompt), and finally, on Windows, the
+    /// `SOFTWARE\Microsoft\Windows Kits\Installed Roots` registry key.
     pub fn new() -> Result<Self, Error> {
+        if let Some(dir) =
+            std::env::var_os("WINDOWS_KITS_10").or_else(|| std::env::var_os("WindowsSdkDir"))
+        {
+            return Ok(Self::from_path(dir));
+        }
+
+        Self::from_registry()
+    }
+
+    /// Sets up a new `WindowsKits` instance rooted at an explicit path, skipping both the
+    /// environment variables and the registry entirely. This is what allows constructing a
+    /// `WindowsKits` on non-Windows hosts, e.g. to point a cross-compiling toolchain at an SDK
+    /// shipped as a plain directory tree.
+    pub fn from_path(root: impl Into<PathBuf>) -> Self {
+        Self { path: root.into() }
+    }
+
+    #[cfg(windows)]
+    fn from_registry() -> Result<Self, Error> {
         let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
         let key = r"SOFTWARE\Microsoft\Windows Kits\Installed Roots";
         let dir: String = hklm.open_subkey(key)?.get_value("KitsRoot10")?;
 
-        Ok(Self {
-            path: dir.into(),
-        })
+        Ok(Self::from_path(dir))
+    }
+
+    #[cfg(not(windows))]
+    fn from_registry() -> Result<Self, Error> {
+        Err(Error::DirectoryNotFound)
     }
 
     /// Returns the path to the Windows Kits directory. The default should be
@@ -55,23 +203,209 @@ impl WindowsKits {
 
     /// Retrieves the path to the directory for the given [`DirectoryType`] joined by the version
     /// directory, which is selected by enumerating the version directories and picking the highest
-    /// version.
+    /// version, parsed and compared numerically rather than as a string.
     pub fn get_version_dir(&self, directory_type: DirectoryType) -> Result<PathBuf, Error> {
         let dir = self.get_dir(directory_type).read_dir()?;
 
         let path = dir
             .filter_map(|dir| dir.ok())
             .map(|dir| dir.path())
-            .filter(|dir| {
-                dir.components()
-                    .last()
-                    .and_then(|c| c.as_os_str().to_str())
-                    .map(|c| c.starts_with("10."))
-                    .unwrap_or(false)
-            })
-            .max()
+            .filter(|dir| SdkVersion::from_path(dir).is_some())
+            .max_by_key(|dir| SdkVersion::from_path(dir))
             .ok_or(Error::DirectoryNotFound)?;
 
         Ok(path)
     }
+
+    /// Retrieves the path to the directory for the given [`DirectoryType`] joined by the
+    /// requested `version`, without regard to whether it is the highest version available. This
+    /// is useful for pinning a specific SDK version for reproducible builds.
+    pub fn get_version_dir_exact(
+        &self,
+        version: &SdkVersion,
+        directory_type: DirectoryType,
+    ) -> Result<PathBuf, Error> {
+        let path = self.get_dir(directory_type).join(version.to_string());
+
+        if !path.is_dir() {
+            return Err(Error::DirectoryNotFound);
+        }
+
+        Ok(path)
+    }
+
+    /// Enumerates the installed SDK versions for the given [`DirectoryType`].
+    pub fn list_versions(&self, directory_type: DirectoryType) -> Result<Vec<SdkVersion>, Error> {
+        let dir = self.get_dir(directory_type).read_dir()?;
+
+        let mut versions: Vec<SdkVersion> = dir
+            .filter_map(|dir| dir.ok())
+            .filter_map(|dir| SdkVersion::from_path(&dir.path()))
+            .collect();
+
+        versions.sort();
+
+        Ok(versions)
+    }
+
+    /// Retrieves the path to the architecture-specific library directory for the given
+    /// [`Component`] of the highest installed SDK version, i.e. `Lib\<ver>\<component>\<arch>`.
+    pub fn get_lib_dir(&self, component: Component, arch: Arch) -> Result<PathBuf, Error> {
+        let path = self
+            .get_version_dir(DirectoryType::Libraries)?
+            .join(component.as_dir_name())
+            .join(arch.as_dir_name());
+
+        if !path.is_dir() {
+            return Err(Error::DirectoryNotFound);
+        }
+
+        Ok(path)
+    }
+
+    /// Retrieves the path to the include directory for the given [`Component`] of the highest
+    /// installed SDK version, i.e. `Include\<ver>\<component>`.
+    pub fn get_include_dir(&self, component: Component) -> Result<PathBuf, Error> {
+        let path = self
+            .get_version_dir(DirectoryType::Headers)?
+            .join(component.as_dir_name());
+
+        if !path.is_dir() {
+            return Err(Error::DirectoryNotFound);
+        }
+
+        Ok(path)
+    }
+
+    /// Retrieves the path to the architecture-specific binaries directory of the highest
+    /// installed SDK version, i.e. `bin\<ver>\<arch>`.
+    pub fn get_bin_dir(&self, arch: Arch) -> Result<PathBuf, Error> {
+        let path = self
+            .get_version_dir(DirectoryType::Binaries)?
+            .join(arch.as_dir_name());
+
+        if !path.is_dir() {
+            return Err(Error::DirectoryNotFound);
+        }
+
+        Ok(path)
+    }
+
+    /// Builds the `INCLUDE`, `LIB`, and `PATH` environment variable values needed to invoke
+    /// `cl.exe`/`link.exe` for the given architecture outside a Developer Command Prompt,
+    /// joining the `ucrt`, `um`, and `shared` component directories of the highest installed
+    /// SDK version.
+    pub fn environment(&self, arch: Arch) -> Result<Vec<(OsString, OsString)>, Error> {
+        let include = Self::join_paths(&[
+            self.get_include_dir(Component::Ucrt)?,
+            self.get_include_dir(Component::Um)?,
+            self.get_include_dir(Component::Shared)?,
+        ]);
+
+        let lib = Self::join_paths(&[
+            self.get_lib_dir(Component::Ucrt, arch)?,
+            self.get_lib_dir(Component::Um, arch)?,
+        ]);
+
+        let path = self.get_bin_dir(arch)?.into_os_string();
+
+        Ok(vec![
+            (OsString::from("INCLUDE"), include),
+            (OsString::from("LIB"), lib),
+            (OsString::from("PATH"), path),
+        ])
+    }
+
+    /// Locates an SDK tool, e.g. `rc.exe`, `mt.exe`, `signtool.exe` or `midl.exe`, in the
+    /// architecture-specific binaries directory of the highest installed SDK version.
+    pub fn find_tool(&self, name: &str, arch: Arch) -> Result<PathBuf, Error> {
+        let path = self.get_bin_dir(arch)?.join(name);
+
+        if !path.is_file() {
+            return Err(Error::DirectoryNotFound);
+        }
+
+        Ok(path)
+    }
+
+    fn join_paths(paths: &[PathBuf]) -> OsString {
+        let mut joined = OsString::new();
+
+        for (i, path) in paths.iter().enumerate() {
+            if i > 0 {
+                joined.push(";");
+            }
+
+            joined.push(path.as_os_str());
+        }
+
+        joined
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sdk_version_orders_numerically_not_lexicographically() {
+        let older: SdkVersion = "10.0.9600.0".parse().unwrap();
+        let newer: SdkVersion = "10.0.19041.0".parse().unwrap();
+
+        assert!(newer > older);
+    }
+
+    #[test]
+    fn sdk_version_rejects_trailing_garbage() {
+        assert!("10.0.19041.0.backup".parse::<SdkVersion>().is_err());
+        assert!("10.0.19041.0-preview".parse::<SdkVersion>().is_err());
+    }
+
+    #[test]
+    fn sdk_version_from_path_requires_10_prefix() {
+        assert!(SdkVersion::from_path(Path::new("1.2.3.4")).is_none());
+        assert!(SdkVersion::from_path(Path::new("10.0.19041.0")).is_some());
+    }
+
+    #[test]
+    fn arch_from_target_triple_maps_known_triples() {
+        assert_eq!(
+            Arch::from_target_triple("x86_64-pc-windows-msvc"),
+            Some(Arch::X64)
+        );
+        assert_eq!(
+            Arch::from_target_triple("i686-pc-windows-msvc"),
+            Some(Arch::X86)
+        );
+        assert_eq!(
+            Arch::from_target_triple("aarch64-pc-windows-msvc"),
+            Some(Arch::Arm64)
+        );
+        assert_eq!(
+            Arch::from_target_triple("thumbv7a-pc-windows-msvc"),
+            Some(Arch::Arm)
+        );
+        assert_eq!(Arch::from_target_triple("riscv64gc-unknown-linux-gnu"), None);
+    }
+
+    #[test]
+    fn new_prefers_windows_kits_10_env_over_windows_sdk_dir_env() {
+        std::env::set_var("WINDOWS_KITS_10", "/opt/sdk-a");
+        std::env::set_var("WindowsSdkDir", "/opt/sdk-b");
+
+        assert_eq!(WindowsKits::new().unwrap().path(), PathBuf::from("/opt/sdk-a"));
+
+        std::env::remove_var("WINDOWS_KITS_10");
+        assert_eq!(WindowsKits::new().unwrap().path(), PathBuf::from("/opt/sdk-b"));
+
+        std::env::remove_var("WindowsSdkDir");
+    }
+
+    #[test]
+    fn from_path_bypasses_env_and_registry() {
+        assert_eq!(
+            WindowsKits::from_path("/opt/sdk-c").path(),
+            PathBuf::from("/opt/sdk-c")
+        );
+    }
 }